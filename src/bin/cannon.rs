@@ -1,7 +1,9 @@
 use ray_tracer::{Canvas, Colour, Tuple};
+use std::env;
 use std::fmt;
 use std::fs::File;
 use std::io::prelude::*;
+use std::process;
 
 struct Projectile {
     position: Tuple,
@@ -20,38 +22,109 @@ impl fmt::Debug for Projectile {
     }
 }
 
-static WIDTH: u32 = 400;
-static HEIGHT: u32 = 200;
+struct Options {
+    width: u32,
+    height: u32,
+    velocity: f32,
+    gravity: f32,
+    wind: f32,
+    output: String,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            width: 400,
+            height: 200,
+            velocity: 1.0,
+            gravity: 0.003,
+            wind: 0.001,
+            output: String::from("cannon.ppm"),
+        }
+    }
+}
+
+// tiny gravity can leave the projectile hanging in the air for a very long time
+const MAX_TICKS: u32 = 100_000;
+
+static USAGE: &str = "Usage: cannon [--width N] [--height N] [--velocity F] \
+                      [--gravity F] [--wind F] [--output PATH]";
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = env::args().skip(1);
+
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or(format!("Missing value for {}", flag))?;
+        let invalid = || format!("Invalid value for {}: {}", flag, value);
+
+        match flag.as_str() {
+            "--width" => options.width = value.parse().map_err(|_| invalid())?,
+            "--height" => options.height = value.parse().map_err(|_| invalid())?,
+            "--velocity" => options.velocity = value.parse().map_err(|_| invalid())?,
+            "--gravity" => options.gravity = value.parse().map_err(|_| invalid())?,
+            "--wind" => options.wind = value.parse().map_err(|_| invalid())?,
+            "--output" => options.output = value,
+            _ => return Err(format!("Unknown argument: {}", flag)),
+        }
+    }
+
+    // without a downward pull the projectile never lands and the loop never ends
+    if !options.gravity.is_finite() || options.gravity <= 0.0 {
+        return Err(format!("Gravity must be positive: {}", options.gravity));
+    }
+
+    if !options.velocity.is_finite() {
+        return Err(format!("Velocity must be finite: {}", options.velocity));
+    }
+
+    if !options.wind.is_finite() {
+        return Err(format!("Wind must be finite: {}", options.wind));
+    }
+
+    Ok(options)
+}
 
 fn main() {
-    let mut canvas = Canvas::new(WIDTH, HEIGHT);
+    let options = parse_args().unwrap_or_else(|err| {
+        eprintln!("{}\n{}", err, USAGE);
+        process::exit(1);
+    });
+
+    let mut canvas = Canvas::new(options.width, options.height);
 
     let mut projectile = Projectile {
         position: Tuple::point(1.0, 1.0, 0.0),
-        velocity: Tuple::vector(5.0, 6.0, 0.0).normalise() * 1.0,
+        velocity: Tuple::vector(5.0, 6.0, 0.0).normalise() * options.velocity,
     };
 
     let environment = Environment {
-        gravity: Tuple::vector(0.0, -0.3, 0.0) * 0.01,
-        wind: Tuple::vector(-0.001, 0.0, 0.0),
+        gravity: Tuple::vector(0.0, -options.gravity, 0.0),
+        wind: Tuple::vector(-options.wind, 0.0, 0.0),
     };
 
     let colour = Colour::new(0.1, 1.0, 0.0);
 
-    while projectile.position.y >= 0.0 && projectile.position.x >= 0.0 {
+    let on_canvas = |position: &Tuple| {
+        position.y >= 0.0 && position.x >= 0.0 && position.x < options.width as f32
+    };
+
+    for _ in 0..MAX_TICKS {
+        if !on_canvas(&projectile.position) {
+            break;
+        }
+
         projectile = tick(&environment, &projectile);
 
         let x = projectile.position.x as u32;
-        let y = HEIGHT - (projectile.position.y as u32).min(HEIGHT);
+        let y = options.height - (projectile.position.y as u32).min(options.height);
 
-        if x < WIDTH && y < HEIGHT {
-            canvas.write_pixel(x, y, &colour);
-        }
+        canvas.fill_rect(x, y, 1, 1, &colour);
     }
 
     let ppm = canvas.to_ppm();
 
-    let mut output_file = File::create("cannon.ppm").unwrap();
+    let mut output_file = File::create(&options.output).unwrap();
     output_file.write_all(&ppm.into_bytes()).unwrap();
 }
 
@@ -1,31 +1,41 @@
-use ray_tracer::{Canvas, Colour, Tuple, Ray, Sphere, sphere};
+use ray_tracer::{Canvas, Colour, Material, PointLight, Tuple, Ray, Sphere, light, sphere};
 use std::fs::File;
 use std::io::prelude::*;
 
-static WIDTH: u32 = 400;
-static HEIGHT: u32 = 400;
+static CANVAS_SIZE: u32 = 400;
+static WALL_Z: f32 = 10.0;
+static WALL_SIZE: f32 = 7.0;
 
 fn main() {
-    let mut canvas = Canvas::new(WIDTH, HEIGHT);
-    let sphere = Sphere::new();
-    let light = Tuple::point(0.0, 0.0, -3.0);
-
-    let ratio = 400.0 / 6.0;
-
-    for x in 0..WIDTH {
-        for y in 0..HEIGHT {
-            let direction = Tuple::vector(
-                (x as f32) / ratio - 3.0,
-                (y as f32) / ratio - 3.0,
-                4.0
-            );
-            let ray = Ray::new(light, direction);
+    let mut canvas = Canvas::new(CANVAS_SIZE, CANVAS_SIZE);
+
+    let sphere = Sphere::new()
+        .with_material(Material::new().with_colour(Colour::new(1.0, 0.2, 1.0)));
+
+    let light = PointLight::new(Tuple::point(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+
+    let ray_origin = Tuple::point(0.0, 0.0, -5.0);
+    let pixel_size = WALL_SIZE / CANVAS_SIZE as f32;
+    let half = WALL_SIZE / 2.0;
+
+    for y in 0..CANVAS_SIZE {
+        // world y goes up while canvas y goes down
+        let world_y = half - pixel_size * y as f32;
+
+        for x in 0..CANVAS_SIZE {
+            let world_x = -half + pixel_size * x as f32;
+            let position = Tuple::point(world_x, world_y, WALL_Z);
+
+            let ray = Ray::new(ray_origin, (position - ray_origin).normalise());
             let xs = sphere.intersect(&ray);
 
-            if !xs.is_empty() {
-                let hit = sphere::hit(&xs);
-                let t = hit.unwrap().t;
-                let colour = Colour::new(t, t, t);
+            if let Some(hit) = sphere::hit(&xs) {
+                let point = ray.position(hit.t);
+                let normal = hit.object.normal_at(&point);
+                let eye = -ray.direction;
+                let material = hit.object.material_facing(&eye, &normal);
+
+                let colour = light::lighting(material, &light, &point, &eye, &normal);
                 canvas.write_pixel(x, y, &colour);
             }
         }
@@ -1,38 +1,111 @@
 use ray_tracer::{Canvas, Colour, Tuple, Matrix};
+use std::env;
 use std::fs::File;
 use std::io::prelude::*;
 use std::f32::consts::PI;
+use std::process;
 
-static WIDTH: u32 = 400;
-static HEIGHT: u32 = 400;
+struct Options {
+    width: u32,
+    height: u32,
+    time: Option<(u32, u32)>,
+    output: String,
+}
 
-fn draw_pixel(canvas: &mut Canvas, pixel: &Tuple) {
-    let white = Colour::new(1.0, 1.0, 1.0);
-    let x = pixel.x as u32;
-    let y = pixel.y as u32;
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            width: 400,
+            height: 400,
+            time: None,
+            output: String::from("clock.ppm"),
+        }
+    }
+}
+
+static USAGE: &str = "Usage: clock [--width N] [--height N] [--time HH:MM] [--output PATH]";
+
+fn parse_time(value: &str) -> Option<(u32, u32)> {
+    let mut parts = value.splitn(2, ':');
+    let hours: u32 = parts.next()?.parse().ok()?;
+    let minutes: u32 = parts.next()?.parse().ok()?;
+
+    if hours < 24 && minutes < 60 {
+        Some((hours, minutes))
+    } else {
+        None
+    }
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = env::args().skip(1);
+
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or(format!("Missing value for {}", flag))?;
+        let invalid = || format!("Invalid value for {}: {}", flag, value);
+
+        match flag.as_str() {
+            "--width" => options.width = value.parse().map_err(|_| invalid())?,
+            "--height" => options.height = value.parse().map_err(|_| invalid())?,
+            "--time" => options.time = Some(parse_time(&value).ok_or_else(invalid)?),
+            "--output" => options.output = value,
+            _ => return Err(format!("Unknown argument: {}", flag)),
+        }
+    }
 
-    canvas.write_pixel(x, y, &white);
-    canvas.write_pixel(x + 1, y, &white);
-    canvas.write_pixel(x, y + 1, &white);
-    canvas.write_pixel(x + 1, y + 1, &white);
+    Ok(options)
+}
+
+fn draw_pixel(canvas: &mut Canvas, pixel: &Tuple, colour: &Colour) {
+    if pixel.x >= 0.0 && pixel.y >= 0.0 {
+        canvas.fill_rect(pixel.x as u32, pixel.y as u32, 2, 2, colour);
+    }
+}
+
+fn draw_hand(canvas: &mut Canvas, centre: &Matrix, length: f32, angle: f32, colour: &Colour) {
+    let steps = (length / 4.0) as u32;
+
+    for step in 0..=steps {
+        let transformation = centre * Matrix::rotation_z(angle);
+        let pixel = transformation * Tuple::point(0.0, -(step as f32) * 4.0, 0.0);
+        draw_pixel(canvas, &pixel, colour);
+    }
 }
 
 fn main() {
-    let mut canvas = Canvas::new(WIDTH, HEIGHT);
+    let options = parse_args().unwrap_or_else(|err| {
+        eprintln!("{}\n{}", err, USAGE);
+        process::exit(1);
+    });
+
+    let mut canvas = Canvas::new(options.width, options.height);
+    let white = Colour::new(1.0, 1.0, 1.0);
+
+    let radius = options.width.min(options.height) as f32 * 3.0 / 8.0;
+    let centre = Matrix::translation(options.width as f32 / 2.0, options.height as f32 / 2.0, 0.0);
 
     for i in 0..12 {
         let transformation =
             Matrix::identity()
-            .translate(0.0, -150.0, 0.0)
+            .translate(0.0, -radius, 0.0)
             .rotate_z(PI / 6.0 * i as f32)
-            .translate(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0, 0.0);
+            .translate(options.width as f32 / 2.0, options.height as f32 / 2.0, 0.0);
 
         let pixel = transformation * Tuple::point(0.0, 0.0, 0.0);
-        draw_pixel(&mut canvas, &pixel);
+        draw_pixel(&mut canvas, &pixel, &white);
+    }
+
+    if let Some((hours, minutes)) = options.time {
+        let minute_angle = PI / 30.0 * minutes as f32;
+        let hour_angle = PI / 6.0 * (hours % 12) as f32 + minute_angle / 12.0;
+
+        draw_hand(&mut canvas, &centre, radius * 0.5, hour_angle, &Colour::new(1.0, 0.2, 0.2));
+        draw_hand(&mut canvas, &centre, radius * 0.8, minute_angle, &Colour::new(0.2, 0.6, 1.0));
     }
 
     let ppm = canvas.to_ppm();
 
-    let mut output_file = File::create("clock.ppm").unwrap();
+    let mut output_file = File::create(&options.output).unwrap();
     output_file.write_all(&ppm.into_bytes()).unwrap();
 }
@@ -0,0 +1,40 @@
+use ray_tracer::Canvas;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+static USAGE: &str = "Usage: ppm2png INPUT.ppm [OUTPUT.png]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let (input, output) = match args.as_slice() {
+        [input] => (input.clone(), Path::new(input).with_extension("png")),
+        [input, output] => (input.clone(), Path::new(output).to_path_buf()),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
+    };
+
+    let ppm = fs::read_to_string(&input).unwrap_or_else(|err| {
+        eprintln!("Could not read {}: {}", input, err);
+        process::exit(1);
+    });
+
+    let canvas = Canvas::from_ppm(&ppm).unwrap_or_else(|err| {
+        eprintln!("Could not parse {}: {}", input, err);
+        process::exit(1);
+    });
+
+    let png = canvas.to_png().unwrap_or_else(|err| {
+        eprintln!("Could not encode {} as PNG: {}", input, err);
+        process::exit(1);
+    });
+
+    if let Err(err) = fs::write(&output, png) {
+        eprintln!("Could not write {}: {}", output.display(), err);
+        process::exit(1);
+    }
+}
@@ -1,4 +1,49 @@
 use crate::colour::Colour;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum PpmError {
+    UnsupportedFormat,
+    MissingValue,
+    InvalidNumber(String),
+    InvalidDimensions,
+    TooManyValues,
+}
+
+impl fmt::Display for PpmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PpmError::UnsupportedFormat => write!(f, "only plain (P3) PPM files are supported"),
+            PpmError::MissingValue => write!(f, "unexpected end of PPM data"),
+            PpmError::InvalidNumber(token) => write!(f, "invalid number in PPM data: {}", token),
+            PpmError::InvalidDimensions => write!(f, "PPM dimensions are too large"),
+            PpmError::TooManyValues => write!(f, "PPM data has more values than pixels"),
+        }
+    }
+}
+
+impl std::error::Error for PpmError {}
+
+#[derive(Debug, PartialEq)]
+pub enum CanvasError {
+    TooLarge { width: u32, height: u32 },
+    WrongBufferSize { expected: usize, actual: usize },
+}
+
+impl fmt::Display for CanvasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CanvasError::TooLarge { width, height } => {
+                write!(f, "cannot allocate a {}x{} canvas", width, height)
+            }
+            CanvasError::WrongBufferSize { expected, actual } => {
+                write!(f, "expected a buffer of {} bytes but got {}", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CanvasError {}
 
 pub struct Canvas {
     pixels: Vec<Colour>,
@@ -8,23 +53,68 @@ pub struct Canvas {
 
 impl Canvas {
     pub fn new(width: u32, height: u32) -> Self {
-        let pixel_count = width * height;
+        match Canvas::try_new(width, height) {
+            Ok(canvas) => canvas,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    pub fn try_new(width: u32, height: u32) -> Result<Self, CanvasError> {
+        let too_large = || CanvasError::TooLarge { width, height };
+
+        let pixel_count = (width as usize)
+            .checked_mul(height as usize)
+            .ok_or_else(too_large)?;
+
+        // fails on capacity overflow as well as when the allocation itself fails
+        let mut pixels = Vec::new();
+        pixels
+            .try_reserve_exact(pixel_count)
+            .map_err(|_| too_large())?;
+
         let black = Colour::new(0.0, 0.0, 0.0);
-        Canvas {
-            pixels: vec![black; pixel_count as usize],
+        pixels.resize(pixel_count, black);
+
+        Ok(Canvas {
+            pixels,
             width,
             height,
-        }
+        })
     }
 
     pub fn write_pixel(&mut self, x: u32, y: u32, colour: &Colour) {
-        let index = y * self.width + x;
-        self.pixels[index as usize] = *colour;
+        let index = self.index(x, y);
+        self.pixels[index] = *colour;
     }
 
     pub fn read_pixel(&self, x: u32, y: u32) -> Colour {
-        let index = y * self.width + x;
-        self.pixels[index as usize]
+        self.pixels[self.index(x, y)]
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        assert!(x < self.width, "x coordinate {} is outside the canvas", x);
+        assert!(y < self.height, "y coordinate {} is outside the canvas", y);
+
+        y as usize * self.width as usize + x as usize
+    }
+
+    pub fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, colour: &Colour) {
+        let x_end = x.saturating_add(width).min(self.width);
+        let y_end = y.saturating_add(height).min(self.height);
+
+        for row in y..y_end {
+            for col in x..x_end {
+                self.write_pixel(col, row, colour);
+            }
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
     }
 
     pub fn to_ppm(&self) -> String {
@@ -32,7 +122,7 @@ impl Canvas {
 
         let mut data = String::new();
 
-        let mut row_numbers = Vec::with_capacity((self.width * 3) as usize);
+        let mut row_numbers = Vec::with_capacity(self.width as usize * 3);
 
         for row in 0..self.height {
             for col in 0..self.width {
@@ -40,12 +130,163 @@ impl Canvas {
                 row_numbers.extend_from_slice(&colour_strings);
             }
             data.push_str(&combine_numbers(&row_numbers));
-            data.push_str("\n");
+            data.push('\n');
             row_numbers.clear();
         }
 
         header + &data
     }
+
+    pub fn from_ppm(ppm: &str) -> Result<Canvas, PpmError> {
+        // comments run from a '#' to the end of the line
+        let mut tokens = ppm
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or(""))
+            .flat_map(str::split_whitespace);
+
+        if tokens.next() != Some("P3") {
+            return Err(PpmError::UnsupportedFormat);
+        }
+
+        let mut next_number = || -> Result<u32, PpmError> {
+            let token = tokens.next().ok_or(PpmError::MissingValue)?;
+            token.parse().map_err(|_| PpmError::InvalidNumber(token.to_owned()))
+        };
+
+        let width = next_number()?;
+        let height = next_number()?;
+        let max_value = next_number()?;
+        if max_value == 0 {
+            return Err(PpmError::InvalidNumber(max_value.to_string()));
+        }
+        let scale = max_value as f32;
+
+        // every value takes at least a digit and a separator, so a header that
+        // promises more data than the input could hold is rejected before allocating
+        let values = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .ok_or(PpmError::InvalidDimensions)?;
+        if values > 0 && ppm.len() < values * 2 - 1 {
+            return Err(PpmError::MissingValue);
+        }
+
+        let mut canvas = Canvas::try_new(width, height).map_err(|_| PpmError::InvalidDimensions)?;
+        for row in 0..height {
+            for col in 0..width {
+                let r = next_number()? as f32 / scale;
+                let g = next_number()? as f32 / scale;
+                let b = next_number()? as f32 / scale;
+                canvas.write_pixel(col, row, &Colour::new(r, g, b));
+            }
+        }
+
+        match next_number() {
+            Err(PpmError::MissingValue) => Ok(canvas),
+            Err(err) => Err(err),
+            Ok(_) => Err(PpmError::TooManyValues),
+        }
+    }
+
+    pub fn as_rgba8(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.pixels.len() * 4);
+        for pixel in &self.pixels {
+            data.extend_from_slice(&[
+                convert_pixel(pixel.r),
+                convert_pixel(pixel.g),
+                convert_pixel(pixel.b),
+                255,
+            ]);
+        }
+        data
+    }
+
+    pub fn from_rgba8(data: &[u8], width: u32, height: u32) -> Result<Canvas, CanvasError> {
+        // check the buffer before allocating so a bogus size can't trigger a huge allocation
+        let expected = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or(CanvasError::TooLarge { width, height })?;
+
+        if data.len() != expected {
+            return Err(CanvasError::WrongBufferSize { expected, actual: data.len() });
+        }
+
+        let mut canvas = Canvas::try_new(width, height)?;
+
+        // alpha is dropped as the canvas has no alpha channel
+        for (pixel, rgba) in canvas.pixels.iter_mut().zip(data.chunks_exact(4)) {
+            *pixel = Colour::new(
+                rgba[0] as f32 / 255.0,
+                rgba[1] as f32 / 255.0,
+                rgba[2] as f32 / 255.0,
+            );
+        }
+
+        Ok(canvas)
+    }
+
+    pub fn as_f32_slice(&self) -> &[f32] {
+        let len = self.pixels.len() * 3;
+        // SAFETY: Colour is repr(C) with exactly three f32 fields, so a slice of
+        // colours has the same layout as a slice of three times as many f32s
+        unsafe { std::slice::from_raw_parts(self.pixels.as_ptr() as *const f32, len) }
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let mut data = Vec::with_capacity(self.pixels.len() * 3);
+        for pixel in &self.pixels {
+            data.push(convert_pixel(pixel.r));
+            data.push(convert_pixel(pixel.g));
+            data.push(convert_pixel(pixel.b));
+        }
+
+        let mut output = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut output, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            // PNG does not allow zero width or height, so empty canvases fail here
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&data)?;
+            writer.finish()?;
+        }
+
+        Ok(output)
+    }
+}
+
+// canvases larger than this are summarised rather than dumped pixel by pixel
+const MAX_DISPLAY_SIZE: u32 = 32;
+
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Canvas {}x{}", self.width, self.height)?;
+
+        if self.width > MAX_DISPLAY_SIZE || self.height > MAX_DISPLAY_SIZE {
+            return Ok(());
+        }
+
+        for row in 0..self.height {
+            writeln!(f)?;
+            for col in 0..self.width {
+                if col > 0 {
+                    write!(f, " ")?;
+                }
+                let colour = self.read_pixel(col, row);
+                write!(
+                    f,
+                    "{:02x}{:02x}{:02x}",
+                    convert_pixel(colour.r),
+                    convert_pixel(colour.g),
+                    convert_pixel(colour.b)
+                )?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 fn ppm_header(width: u32, height: u32) -> String {
@@ -60,7 +301,7 @@ fn format_colour(colour: &Colour) -> [String; 3] {
 }
 
 fn convert_pixel(pixel: f32) -> u8 {
-    (pixel.min(1.0).max(0.0) * 255.0).round() as u8
+    (pixel.clamp(0.0, 1.0) * 255.0).round() as u8
 }
 
 fn combine_numbers(numbers: &[String]) -> String {
@@ -73,11 +314,11 @@ fn combine_numbers(numbers: &[String]) -> String {
         }
 
         current_line.push_str(n);
-        current_line.push_str(" ");
+        current_line.push(' ');
     }
 
     let trimmed = current_line.trim();
-    if trimmed != "" {
+    if !trimmed.is_empty() {
         lines.push(trimmed.to_owned());
     }
 
@@ -127,6 +368,71 @@ mod tests {
         assert_eq!(canvas.read_pixel(0, 1), red);
     }
 
+    #[test]
+    fn try_new_rejects_absurd_sizes() {
+        let result = Canvas::try_new(u32::MAX, u32::MAX);
+        assert_eq!(
+            result.err(),
+            Some(CanvasError::TooLarge { width: u32::MAX, height: u32::MAX })
+        );
+    }
+
+    #[test]
+    fn try_new_accepts_empty_canvas() {
+        let canvas = Canvas::try_new(0, 100).unwrap();
+        assert_eq!(canvas.pixels.len(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn write_pixel_outside_canvas_panics() {
+        let mut canvas = Canvas::new(2, 2);
+        // would otherwise silently wrap onto the next row
+        canvas.write_pixel(2, 0, &Colour::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut canvas = Canvas::new(3, 3);
+        let red = Colour::new(1.0, 0.0, 0.0);
+        let black = Colour::new(0.0, 0.0, 0.0);
+
+        canvas.fill_rect(1, 0, 2, 2, &red);
+
+        assert_eq!(canvas.read_pixel(0, 0), black);
+        assert_eq!(canvas.read_pixel(1, 0), red);
+        assert_eq!(canvas.read_pixel(2, 1), red);
+        assert_eq!(canvas.read_pixel(1, 2), black);
+    }
+
+    #[test]
+    fn test_fill_rect_clips_to_canvas() {
+        let mut canvas = Canvas::new(2, 2);
+        let red = Colour::new(1.0, 0.0, 0.0);
+
+        canvas.fill_rect(1, 1, 5, 5, &red);
+        canvas.fill_rect(5, 5, 1, 1, &red);
+
+        assert_eq!(canvas.read_pixel(1, 1), red);
+    }
+
+    #[test]
+    fn test_display_dumps_small_canvas() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.write_pixel(1, 0, &Colour::new(1.0, 0.5, 0.0));
+        canvas.write_pixel(0, 1, &Colour::new(0.0, 0.0, 2.0));
+
+        let expected = "Canvas 2x2\n000000 ff8000\n0000ff 000000";
+
+        assert_eq!(canvas.to_string(), expected);
+    }
+
+    #[test]
+    fn test_display_summarises_large_canvas() {
+        let canvas = Canvas::new(100, 2);
+        assert_eq!(canvas.to_string(), "Canvas 100x2");
+    }
+
     #[test]
     fn test_to_ppm_writes_ppm_header() {
         let canvas = Canvas::new(2, 2);
@@ -181,6 +487,145 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn test_from_ppm_reads_pixels() {
+        let ppm = "P3\n2 1\n255\n255 0 0 0 51 255\n";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!(canvas.width, 2);
+        assert_eq!(canvas.height, 1);
+        assert_eq!(canvas.read_pixel(0, 0), Colour::new(1.0, 0.0, 0.0));
+        assert_eq!(canvas.read_pixel(1, 0), Colour::new(0.0, 0.2, 1.0));
+    }
+
+    #[test]
+    fn test_from_ppm_scales_by_max_value() {
+        let ppm = "P3\n1 1\n100\n50 100 0\n";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!(canvas.read_pixel(0, 0), Colour::new(0.5, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_from_ppm_ignores_comments() {
+        let ppm = "P3\n# made by hand\n1 1 # size\n255\n# pixels follow\n255 0 51\n";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!(canvas.read_pixel(0, 0), Colour::new(1.0, 0.0, 0.2));
+    }
+
+    #[test]
+    fn test_from_ppm_reads_wrapped_rows() {
+        let mut canvas = Canvas::new(10, 2);
+        let c = Colour::new(1.0, 0.8, 0.6);
+        canvas.fill_rect(0, 0, 10, 2, &c);
+
+        let ppm = canvas.to_ppm();
+        let loaded = Canvas::from_ppm(&ppm).unwrap();
+
+        assert_eq!(loaded.width, 10);
+        assert_eq!(loaded.height, 2);
+        assert_eq!(loaded.to_ppm(), ppm);
+    }
+
+    #[test]
+    fn test_ppm_round_trips_every_channel_value() {
+        let mut canvas = Canvas::new(256, 1);
+        for i in 0..256 {
+            let v = i as f32 / 255.0;
+            canvas.write_pixel(i, 0, &Colour::new(v, 1.0 - v, v * v));
+        }
+
+        let ppm = canvas.to_ppm();
+        let loaded = Canvas::from_ppm(&ppm).unwrap();
+
+        assert_eq!(loaded.to_ppm(), ppm);
+        for i in 0..256 {
+            assert_eq!(convert_pixel(loaded.read_pixel(i, 0).r), i as u8);
+        }
+    }
+
+    #[test]
+    fn test_from_ppm_rejects_bad_input() {
+        assert_eq!(Canvas::from_ppm("P6\n1 1\n255\n").err(), Some(PpmError::UnsupportedFormat));
+        assert_eq!(Canvas::from_ppm("P3\n1 1\n255\n0 0").err(), Some(PpmError::MissingValue));
+        assert_eq!(Canvas::from_ppm("P3\n1 1\n255\n0 0 0 0").err(), Some(PpmError::TooManyValues));
+        assert_eq!(
+            Canvas::from_ppm("P3\n1 1\n255\n0 x 0").err(),
+            Some(PpmError::InvalidNumber(String::from("x")))
+        );
+    }
+
+    #[test]
+    fn test_from_ppm_checks_data_before_allocating() {
+        let result = Canvas::from_ppm("P3\n1073741824 4\n255\n0 0 0\n");
+
+        assert_eq!(result.err(), Some(PpmError::MissingValue));
+    }
+
+    #[test]
+    fn test_as_rgba8() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.write_pixel(1, 0, &Colour::new(1.0, 0.5, 2.0));
+
+        assert_eq!(canvas.as_rgba8(), vec![0, 0, 0, 255, 255, 128, 255, 255]);
+    }
+
+    #[test]
+    fn test_from_rgba8() {
+        let data = [255, 0, 51, 10, 0, 255, 0, 255];
+
+        let canvas = Canvas::from_rgba8(&data, 2, 1).unwrap();
+
+        assert_eq!(canvas.read_pixel(0, 0), Colour::new(1.0, 0.0, 0.2));
+        assert_eq!(canvas.read_pixel(1, 0), Colour::new(0.0, 1.0, 0.0));
+        assert_eq!(canvas.as_rgba8(), vec![255, 0, 51, 255, 0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn test_from_rgba8_rejects_wrong_buffer_size() {
+        let data = [0; 7];
+
+        let result = Canvas::from_rgba8(&data, 2, 1);
+
+        assert_eq!(result.err(), Some(CanvasError::WrongBufferSize { expected: 8, actual: 7 }));
+    }
+
+    #[test]
+    fn test_from_rgba8_checks_buffer_before_allocating() {
+        let data = [0; 4];
+
+        let result = Canvas::from_rgba8(&data, 1 << 30, 4);
+
+        assert_eq!(result.err(), Some(CanvasError::WrongBufferSize { expected: 1 << 34, actual: 4 }));
+    }
+
+    #[test]
+    fn test_as_f32_slice() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.write_pixel(0, 0, &Colour::new(0.1, 0.2, 0.3));
+        canvas.write_pixel(1, 0, &Colour::new(1.5, -1.0, 4.0));
+
+        assert_eq!(canvas.as_f32_slice(), &[0.1, 0.2, 0.3, 1.5, -1.0, 4.0]);
+    }
+
+    #[test]
+    fn test_to_png_rejects_empty_canvas() {
+        let canvas = Canvas::from_ppm("P3\n0 5\n255\n").unwrap();
+        assert!(canvas.to_png().is_err());
+    }
+
+    #[test]
+    fn test_to_png_writes_png_signature() {
+        let canvas = Canvas::new(2, 2);
+        let png = canvas.to_png().unwrap();
+
+        assert!(png.starts_with(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']));
+    }
+
     #[test]
     fn test_to_ppm_has_trailing_newline() {
         let canvas = Canvas::new(10, 2);
@@ -2,7 +2,10 @@ use crate::util;
 
 use std::ops;
 
+// repr(C) guarantees the three channels are laid out contiguously, which
+// Canvas::as_f32_slice relies on
 #[derive(Clone, Copy, Debug)]
+#[repr(C)]
 pub struct Colour {
     pub r: f32,
     pub g: f32,
@@ -1,14 +1,19 @@
 pub mod canvas;
 pub mod colour;
 pub mod tuple;
+pub mod light;
+pub mod material;
 pub mod matrix;
+pub mod postprocess;
 pub mod ray;
 pub mod sphere;
 pub mod util;
 
-pub use crate::canvas::Canvas;
+pub use crate::canvas::{Canvas, CanvasError, PpmError};
 pub use crate::colour::Colour;
 pub use crate::tuple::Tuple;
+pub use crate::light::{Falloff, PointLight};
+pub use crate::material::Material;
 pub use crate::matrix::Matrix;
 pub use crate::ray::Ray;
-pub use crate::sphere::Sphere;
+pub use crate::sphere::{Sphere, TransformError};
@@ -0,0 +1,243 @@
+use crate::{Colour, Material, Tuple};
+use std::f32::consts::PI;
+
+// luminous efficacy of an ideal 555nm source, used to turn lumens into watts
+const LUMENS_PER_WATT: f32 = 683.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Falloff {
+    None,
+    InverseSquare,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointLight {
+    pub position: Tuple,
+    pub intensity: Colour,
+    pub falloff: Falloff,
+}
+
+impl PointLight {
+    pub fn new(position: Tuple, intensity: Colour) -> Self {
+        assert!(position.is_point());
+        PointLight { position, intensity, falloff: Falloff::None }
+    }
+
+    // radiant power spread evenly over the sphere, falling off with distance
+    pub fn from_watts(position: Tuple, colour: Colour, watts: f32) -> Self {
+        assert!(position.is_point());
+        let intensity = colour * (watts / (4.0 * PI));
+        PointLight { position, intensity, falloff: Falloff::InverseSquare }
+    }
+
+    pub fn from_lumens(position: Tuple, colour: Colour, lumens: f32) -> Self {
+        PointLight::from_watts(position, colour, lumens / LUMENS_PER_WATT)
+    }
+
+    pub fn intensity_at(&self, point: &Tuple) -> Colour {
+        match self.falloff {
+            Falloff::None => self.intensity,
+            Falloff::InverseSquare => {
+                let to_light = self.position - *point;
+                self.intensity * (1.0 / Tuple::dot(&to_light, &to_light))
+            }
+        }
+    }
+}
+
+pub fn lighting(material: &Material, light: &PointLight, point: &Tuple, eyev: &Tuple, normalv: &Tuple) -> Colour {
+    let black = Colour::new(0.0, 0.0, 0.0);
+
+    // ambient light stands in for indirect bounces, so distance falloff
+    // only applies to the direct diffuse and specular terms
+    let ambient = material.colour * light.intensity * material.ambient;
+
+    // a light sitting exactly on the point has no direction to shade with
+    let lightv = match (light.position - *point).try_normalise() {
+        Some(lightv) => lightv,
+        None => return ambient,
+    };
+
+    let intensity = light.intensity_at(point);
+    let effective_colour = material.colour * intensity;
+
+    // a negative dot product means the light is on the other side of the surface
+    let light_dot_normal = Tuple::dot(&lightv, normalv);
+    if light_dot_normal < 0.0 {
+        return ambient;
+    }
+
+    let diffuse = effective_colour * material.diffuse * light_dot_normal;
+
+    let reflectv = Tuple::reflect(&-lightv, normalv);
+    let reflect_dot_eye = Tuple::dot(&reflectv, eyev);
+    let specular = if reflect_dot_eye <= 0.0 {
+        black
+    } else {
+        let factor = reflect_dot_eye.powf(material.shininess);
+        intensity * material.specular * factor
+    };
+
+    ambient + diffuse + specular
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx_equal(a: Colour, b: Colour) -> bool {
+        (a.r - b.r).abs() < 0.0001 && (a.g - b.g).abs() < 0.0001 && (a.b - b.b).abs() < 0.0001
+    }
+
+    #[test]
+    fn point_light_has_position_and_intensity() {
+        let intensity = Colour::new(1.0, 1.0, 1.0);
+        let position = Tuple::point(0.0, 0.0, 0.0);
+
+        let light = PointLight::new(position, intensity);
+
+        assert_eq!(light.position, position);
+        assert_eq!(light.intensity, intensity);
+    }
+
+    #[test]
+    fn point_light_has_no_falloff_by_default() {
+        let light = PointLight::new(Tuple::point(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0));
+
+        assert_eq!(light.falloff, Falloff::None);
+        assert_eq!(light.intensity_at(&Tuple::point(0.0, 0.0, 10.0)), Colour::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn physical_light_falls_off_with_inverse_square() {
+        let light = PointLight::from_watts(Tuple::point(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0), 4.0 * PI);
+
+        assert_eq!(light.falloff, Falloff::InverseSquare);
+        assert!(approx_equal(light.intensity_at(&Tuple::point(0.0, 1.0, 0.0)), Colour::new(1.0, 1.0, 1.0)));
+        assert!(approx_equal(light.intensity_at(&Tuple::point(0.0, 2.0, 0.0)), Colour::new(0.25, 0.25, 0.25)));
+    }
+
+    #[test]
+    fn lumens_convert_to_watts() {
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let colour = Colour::new(1.0, 0.5, 0.25);
+
+        let from_lumens = PointLight::from_lumens(position, colour, 683.0);
+        let from_watts = PointLight::from_watts(position, colour, 1.0);
+
+        assert!(approx_equal(from_lumens.intensity, from_watts.intensity));
+    }
+
+    #[test]
+    fn lighting_with_physical_light_depends_on_distance() {
+        let m = Material::new();
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let white = Colour::new(1.0, 1.0, 1.0);
+        let near = PointLight::from_watts(Tuple::point(0.0, 0.0, -1.0), white, 4.0 * PI);
+        let far = PointLight::from_watts(Tuple::point(0.0, 0.0, -2.0), white, 4.0 * PI);
+
+        let near_result = lighting(&m, &near, &position, &eyev, &normalv);
+        let far_result = lighting(&m, &far, &position, &eyev, &normalv);
+
+        assert!(approx_equal(near_result, Colour::new(1.9, 1.9, 1.9)));
+        assert!(approx_equal(far_result, Colour::new(0.55, 0.55, 0.55)));
+    }
+
+    #[test]
+    fn ambient_from_physical_light_ignores_distance() {
+        let m = Material::new();
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let white = Colour::new(1.0, 1.0, 1.0);
+        let near = PointLight::from_watts(Tuple::point(0.0, 0.0, 1.0), white, 4.0 * PI);
+        let far = PointLight::from_watts(Tuple::point(0.0, 0.0, 100.0), white, 4.0 * PI);
+
+        // both lights are behind the surface, leaving only the ambient term
+        let near_result = lighting(&m, &near, &position, &eyev, &normalv);
+        let far_result = lighting(&m, &far, &position, &eyev, &normalv);
+
+        assert!(approx_equal(near_result, Colour::new(0.1, 0.1, 0.1)));
+        assert!(approx_equal(far_result, near_result));
+    }
+
+    #[test]
+    fn lighting_with_eye_between_light_and_surface() {
+        let m = Material::new();
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = PointLight::new(Tuple::point(0.0, 0.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+
+        let result = lighting(&m, &light, &position, &eyev, &normalv);
+
+        assert!(approx_equal(result, Colour::new(1.9, 1.9, 1.9)));
+    }
+
+    #[test]
+    fn lighting_with_eye_offset_45_degrees() {
+        let m = Material::new();
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 2_f32.sqrt() / 2.0, -2_f32.sqrt() / 2.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = PointLight::new(Tuple::point(0.0, 0.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+
+        let result = lighting(&m, &light, &position, &eyev, &normalv);
+
+        assert!(approx_equal(result, Colour::new(1.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn lighting_with_light_offset_45_degrees() {
+        let m = Material::new();
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = PointLight::new(Tuple::point(0.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+
+        let result = lighting(&m, &light, &position, &eyev, &normalv);
+
+        assert!(approx_equal(result, Colour::new(0.7364, 0.7364, 0.7364)));
+    }
+
+    #[test]
+    fn lighting_with_eye_in_path_of_reflection() {
+        let m = Material::new();
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, -2_f32.sqrt() / 2.0, -2_f32.sqrt() / 2.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = PointLight::new(Tuple::point(0.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+
+        let result = lighting(&m, &light, &position, &eyev, &normalv);
+
+        assert!(approx_equal(result, Colour::new(1.6364, 1.6364, 1.6364)));
+    }
+
+    #[test]
+    fn lighting_with_light_at_surface_point() {
+        let m = Material::new();
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = PointLight::new(position, Colour::new(1.0, 1.0, 1.0));
+
+        let result = lighting(&m, &light, &position, &eyev, &normalv);
+
+        assert!(approx_equal(result, Colour::new(0.1, 0.1, 0.1)));
+    }
+
+    #[test]
+    fn lighting_with_light_behind_surface() {
+        let m = Material::new();
+        let position = Tuple::point(0.0, 0.0, 0.0);
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+        let light = PointLight::new(Tuple::point(0.0, 0.0, 10.0), Colour::new(1.0, 1.0, 1.0));
+
+        let result = lighting(&m, &light, &position, &eyev, &normalv);
+
+        assert!(approx_equal(result, Colour::new(0.1, 0.1, 0.1)));
+    }
+}
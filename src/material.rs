@@ -0,0 +1,85 @@
+use crate::Colour;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Material {
+    pub colour: Colour,
+    pub ambient: f32,
+    pub diffuse: f32,
+    pub specular: f32,
+    pub shininess: f32,
+}
+
+impl Material {
+    pub fn new() -> Self {
+        Material {
+            colour: Colour::new(1.0, 1.0, 1.0),
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.9,
+            shininess: 200.0,
+        }
+    }
+
+    pub fn with_colour(mut self, colour: Colour) -> Self {
+        self.colour = colour;
+        self
+    }
+
+    pub fn with_ambient(mut self, ambient: f32) -> Self {
+        self.ambient = ambient;
+        self
+    }
+
+    pub fn with_diffuse(mut self, diffuse: f32) -> Self {
+        self.diffuse = diffuse;
+        self
+    }
+
+    pub fn with_specular(mut self, specular: f32) -> Self {
+        self.specular = specular;
+        self
+    }
+
+    pub fn with_shininess(mut self, shininess: f32) -> Self {
+        self.shininess = shininess;
+        self
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_material() {
+        let m = Material::new();
+
+        assert_eq!(m.colour, Colour::new(1.0, 1.0, 1.0));
+        assert_eq!(m.ambient, 0.1);
+        assert_eq!(m.diffuse, 0.9);
+        assert_eq!(m.specular, 0.9);
+        assert_eq!(m.shininess, 200.0);
+    }
+
+    #[test]
+    fn builder_overrides_defaults() {
+        let m = Material::default()
+            .with_colour(Colour::new(1.0, 0.0, 0.0))
+            .with_ambient(0.5)
+            .with_diffuse(0.4)
+            .with_specular(0.3)
+            .with_shininess(10.0);
+
+        assert_eq!(m.colour, Colour::new(1.0, 0.0, 0.0));
+        assert_eq!(m.ambient, 0.5);
+        assert_eq!(m.diffuse, 0.4);
+        assert_eq!(m.specular, 0.3);
+        assert_eq!(m.shininess, 10.0);
+    }
+}
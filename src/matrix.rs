@@ -1,4 +1,5 @@
 use crate::{Tuple, util};
+use std::fmt;
 use std::ops;
 
 #[derive(Clone, Debug)]
@@ -83,26 +84,32 @@ impl Matrix {
     pub fn submatrix(&self, row: u32, col: u32) -> Matrix {
         assert!(self.rows > row && self.cols > col);
 
-        let mut result = self.clone();
+        if self.has_size(4) {
+            let r = remaining_indices(row);
+            let c = remaining_indices(col);
 
-        // remove column
-        let index = col;
-        for i in 0..result.rows {
-            let original_index = index + i * result.cols;
-            // index changes every time item is deleted
-            let adjusted_index = original_index - i;
-            result.data.remove(adjusted_index as usize);
-        }
-        result.cols -= 1;
+            Matrix::new3x3(self.at(r[0], c[0]), self.at(r[0], c[1]), self.at(r[0], c[2]),
+                           self.at(r[1], c[0]), self.at(r[1], c[1]), self.at(r[1], c[2]),
+                           self.at(r[2], c[0]), self.at(r[2], c[1]), self.at(r[2], c[2]))
+        } else if self.has_size(3) {
+            let r = remaining_indices(row);
+            let c = remaining_indices(col);
 
-        // remove row
-        let index = row * result.cols;
-        for _ in 0..result.cols {
-            result.data.remove(index as usize);
-        }
-        result.rows -= 1;
+            Matrix::new2x2(self.at(r[0], c[0]), self.at(r[0], c[1]),
+                           self.at(r[1], c[0]), self.at(r[1], c[1]))
+        } else {
+            let rows = self.rows - 1;
+            let cols = self.cols - 1;
+            let mut data = Vec::with_capacity((rows * cols) as usize);
+
+            for r in (0..self.rows).filter(|&r| r != row) {
+                for c in (0..self.cols).filter(|&c| c != col) {
+                    data.push(self.at(r, c));
+                }
+            }
 
-        result
+            Matrix { rows, cols, data }
+        }
     }
 
     pub fn minor(&self, row: u32, col: u32) -> f32 {
@@ -113,7 +120,7 @@ impl Matrix {
     pub fn cofactor(&self, row: u32, col: u32) -> f32 {
         let minor = self.minor(row, col);
 
-        if (row + col) % 2 == 0 {
+        if (row + col).is_multiple_of(2) {
             minor
         } else {
             -minor
@@ -121,8 +128,14 @@ impl Matrix {
     }
 
     pub fn invert(&self) -> Matrix {
+        self.try_invert().expect("Matrix is not invertible")
+    }
+
+    pub fn try_invert(&self) -> Option<Matrix> {
         let det = self.determinant();
-        assert!(det != 0.0);
+        if det == 0.0 {
+            return None;
+        }
 
         let mut ret = self.clone();
 
@@ -134,7 +147,7 @@ impl Matrix {
             }
         }
 
-        ret
+        Some(ret)
     }
 
     fn row(&self, r: u32) -> Tuple {
@@ -243,6 +256,16 @@ impl Matrix {
     }
 }
 
+// the three indices out of 0..4 that are left after removing one
+fn remaining_indices(removed: u32) -> [u32; 3] {
+    match removed {
+        0 => [1, 2, 3],
+        1 => [0, 2, 3],
+        2 => [0, 1, 3],
+        _ => [0, 1, 2],
+    }
+}
+
 impl PartialEq for Matrix {
     fn eq(&self, other: &Self) -> bool {
         if self.rows != other.rows || self.cols != other.cols {
@@ -258,6 +281,40 @@ impl PartialEq for Matrix {
     }
 }
 
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // a 0x0 matrix (e.g. the submatrix of a 1x1) has no rows to print
+        if self.rows == 0 || self.cols == 0 {
+            return Ok(());
+        }
+
+        let precision = f.precision().unwrap_or(5);
+
+        let cells: Vec<String> = self.data.iter()
+            .map(|value| format!("{:.*}", precision, value))
+            .collect();
+
+        let mut widths = vec![0; self.cols as usize];
+        for (i, cell) in cells.iter().enumerate() {
+            let col = i % self.cols as usize;
+            widths[col] = widths[col].max(cell.len());
+        }
+
+        for (r, row) in cells.chunks(self.cols as usize).enumerate() {
+            if r > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "|")?;
+            for (cell, width) in row.iter().zip(&widths) {
+                write!(f, " {:>width$}", cell, width = width)?;
+            }
+            write!(f, " |")?;
+        }
+
+        Ok(())
+    }
+}
+
 impl ops::Mul<Matrix> for Matrix {
     type Output = Matrix;
 
@@ -305,7 +362,7 @@ impl ops::Mul<Tuple> for Matrix {
     type Output = Tuple;
 
     fn mul(self, rhs: Tuple) -> Self::Output {
-        &self * &rhs
+        &self * rhs
     }
 }
 
@@ -321,17 +378,9 @@ impl ops::Mul<Tuple> for &Matrix {
     type Output = Tuple;
 
     fn mul(self, rhs: Tuple) -> Self::Output {
-        self * &rhs
-    }
-}
-
-impl ops::Mul<&Tuple> for &Matrix {
-    type Output = Tuple;
-
-    fn mul(self, rhs: &Tuple) -> Self::Output {
         assert!(self.rows == 4 && self.cols == 4, "Can only multiply 4x4 matrix with tuple");
 
-        let mut ret = *rhs;
+        let mut ret = rhs;
 
         ret.x = Tuple::dot(&rhs, &self.row(0));
         ret.y = Tuple::dot(&rhs, &self.row(1));
@@ -342,6 +391,14 @@ impl ops::Mul<&Tuple> for &Matrix {
     }
 }
 
+impl ops::Mul<&Tuple> for &Matrix {
+    type Output = Tuple;
+
+    fn mul(self, rhs: &Tuple) -> Self::Output {
+        self * *rhs
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -560,6 +617,32 @@ mod tests {
         assert_eq!(expected, matrix.submatrix(2, 1));
     }
 
+    #[test]
+    fn test_submatrix_of_4x4_removing_last_row_and_column() {
+        let matrix = Matrix::new4x4(1.0, 2.0, 3.0, 4.0,
+                                    2.0, 4.0, 4.0, 2.0,
+                                    8.0, 6.0, 4.0, 1.0,
+                                    0.0, 0.0, 0.0, 1.0);
+
+        let expected = Matrix::new3x3(1.0, 2.0, 3.0,
+                                      2.0, 4.0, 4.0,
+                                      8.0, 6.0, 4.0);
+
+        assert_eq!(expected, matrix.submatrix(3, 3));
+    }
+
+    #[test]
+    fn test_submatrix_of_2x2() {
+        let matrix = Matrix::new2x2(1.0, 2.0,
+                                    3.0, 4.0);
+
+        let sub = matrix.submatrix(0, 1);
+
+        assert_eq!(sub.rows, 1);
+        assert_eq!(sub.cols, 1);
+        assert_eq!(sub.at(0, 0), 3.0);
+    }
+
     #[test]
     fn test_minor_of_3x3() {
         let matrix = Matrix::new3x3(3.0, 5.0, 0.0,
@@ -596,6 +679,18 @@ mod tests {
         matrix.invert();
     }
 
+    #[test]
+    fn test_try_invert_uninvertable_matrix() {
+        let matrix = Matrix::scaling(0.0, 1.0, 1.0);
+        assert_eq!(matrix.try_invert(), None);
+    }
+
+    #[test]
+    fn test_try_invert() {
+        let matrix = Matrix::scaling(2.0, 4.0, 0.5);
+        assert_eq!(matrix.try_invert(), Some(Matrix::scaling(0.5, 0.25, 2.0)));
+    }
+
     #[test]
     fn test_invert() {
         let matrix = Matrix::new4x4(-5.0, 2.0, 6.0, -8.0,
@@ -811,6 +906,32 @@ mod tests {
         assert_eq!(transformation * p, Tuple::point(15.0, 0.0, 7.0));
     }
 
+    #[test]
+    fn display_aligns_columns() {
+        let matrix = Matrix::new2x2(1.0, -20.5,
+                                    300.0, 4.0);
+
+        let expected = "|   1.0 -20.5 |\n| 300.0   4.0 |";
+
+        assert_eq!(format!("{:.1}", matrix), expected);
+    }
+
+    #[test]
+    fn display_defaults_to_five_decimal_places() {
+        let matrix = Matrix::new2x2(0.5, 0.0,
+                                    0.0, 0.5);
+
+        assert_eq!(matrix.to_string(), "| 0.50000 0.00000 |\n| 0.00000 0.50000 |");
+    }
+
+    #[test]
+    fn display_of_empty_matrix_is_empty() {
+        let matrix = Matrix::new2x2(1.0, 2.0,
+                                    3.0, 4.0).submatrix(0, 0).submatrix(0, 0);
+
+        assert_eq!(matrix.to_string(), "");
+    }
+
     fn approx_equal(a: Matrix, b: Matrix) -> bool {
         for i in 0..a.data.len() {
             if (a.data[i] - b.data[i]).abs() > 0.001 {
@@ -0,0 +1,218 @@
+use crate::{Canvas, Colour};
+
+pub fn bloom(canvas: &Canvas, threshold: f32, radius: u32, intensity: f32) -> Canvas {
+    let bright = bright_pass(canvas, threshold);
+    let glow = gaussian_blur(&bright, radius);
+
+    map_pixels(canvas, |x, y, colour| colour + glow.read_pixel(x, y) * intensity)
+}
+
+pub fn bright_pass(canvas: &Canvas, threshold: f32) -> Canvas {
+    let black = Colour::new(0.0, 0.0, 0.0);
+
+    map_pixels(canvas, |_, _, colour| {
+        let brightness = luminance(&colour);
+        if brightness > threshold {
+            colour * ((brightness - threshold) / brightness)
+        } else {
+            black
+        }
+    })
+}
+
+pub fn gaussian_blur(canvas: &Canvas, radius: u32) -> Canvas {
+    if radius == 0 {
+        return map_pixels(canvas, |_, _, colour| colour);
+    }
+
+    let kernel = gaussian_kernel(radius);
+    let width = canvas.width() as i64;
+    let height = canvas.height() as i64;
+
+    let horizontal = map_pixels(canvas, |x, y, _| {
+        convolve(&kernel, |offset| {
+            let sample_x = (x as i64 + offset).clamp(0, width - 1);
+            canvas.read_pixel(sample_x as u32, y)
+        })
+    });
+
+    map_pixels(&horizontal, |x, y, _| {
+        convolve(&kernel, |offset| {
+            let sample_y = (y as i64 + offset).clamp(0, height - 1);
+            horizontal.read_pixel(x, sample_y as u32)
+        })
+    })
+}
+
+pub fn vignette(canvas: &Canvas, strength: f32) -> Canvas {
+    let centre_x = canvas.width() as f32 / 2.0;
+    let centre_y = canvas.height() as f32 / 2.0;
+    let max_distance = (centre_x * centre_x + centre_y * centre_y).sqrt();
+
+    map_pixels(canvas, |x, y, colour| {
+        let dx = x as f32 + 0.5 - centre_x;
+        let dy = y as f32 + 0.5 - centre_y;
+        let distance = (dx * dx + dy * dy).sqrt() / max_distance;
+        let falloff = (1.0 - strength * distance * distance).max(0.0);
+        colour * falloff
+    })
+}
+
+pub fn chromatic_aberration(canvas: &Canvas, amount: f32) -> Canvas {
+    let centre_x = canvas.width() as f32 / 2.0;
+    let centre_y = canvas.height() as f32 / 2.0;
+
+    let sample = |x: u32, y: u32, scale: f32| {
+        let sample_x = centre_x + (x as f32 + 0.5 - centre_x) * scale;
+        let sample_y = centre_y + (y as f32 + 0.5 - centre_y) * scale;
+        let sample_x = sample_x.clamp(0.0, canvas.width() as f32 - 1.0);
+        let sample_y = sample_y.clamp(0.0, canvas.height() as f32 - 1.0);
+        canvas.read_pixel(sample_x as u32, sample_y as u32)
+    };
+
+    map_pixels(canvas, |x, y, colour| {
+        let r = sample(x, y, 1.0 - amount).r;
+        let b = sample(x, y, 1.0 + amount).b;
+        Colour::new(r, colour.g, b)
+    })
+}
+
+fn map_pixels<F>(canvas: &Canvas, f: F) -> Canvas
+where
+    F: Fn(u32, u32, Colour) -> Colour,
+{
+    let mut result = Canvas::new(canvas.width(), canvas.height());
+
+    for y in 0..canvas.height() {
+        for x in 0..canvas.width() {
+            let colour = f(x, y, canvas.read_pixel(x, y));
+            result.write_pixel(x, y, &colour);
+        }
+    }
+
+    result
+}
+
+fn luminance(colour: &Colour) -> f32 {
+    0.2126 * colour.r + 0.7152 * colour.g + 0.0722 * colour.b
+}
+
+fn gaussian_kernel(radius: u32) -> Vec<f32> {
+    let sigma = (radius as f32 / 2.0).max(0.5);
+    let weights: Vec<f32> = (-(radius as i64)..=radius as i64)
+        .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = weights.iter().sum();
+
+    weights.iter().map(|w| w / total).collect()
+}
+
+fn convolve<F>(kernel: &[f32], sample: F) -> Colour
+where
+    F: Fn(i64) -> Colour,
+{
+    let radius = (kernel.len() / 2) as i64;
+
+    kernel
+        .iter()
+        .enumerate()
+        .fold(Colour::new(0.0, 0.0, 0.0), |acc, (i, weight)| {
+            acc + sample(i as i64 - radius) * *weight
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx_equal(a: Colour, b: Colour) -> bool {
+        (a.r - b.r).abs() < 0.0001 && (a.g - b.g).abs() < 0.0001 && (a.b - b.b).abs() < 0.0001
+    }
+
+    #[test]
+    fn bright_pass_removes_dim_pixels() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.write_pixel(0, 0, &Colour::new(0.5, 0.5, 0.5));
+        canvas.write_pixel(1, 0, &Colour::new(4.0, 4.0, 4.0));
+
+        let bright = bright_pass(&canvas, 1.0);
+
+        assert_eq!(bright.read_pixel(0, 0), Colour::new(0.0, 0.0, 0.0));
+        assert!(approx_equal(bright.read_pixel(1, 0), Colour::new(3.0, 3.0, 3.0)));
+    }
+
+    #[test]
+    fn gaussian_kernel_is_normalised() {
+        let kernel = gaussian_kernel(3);
+        let total: f32 = kernel.iter().sum();
+
+        assert_eq!(kernel.len(), 7);
+        assert!((total - 1.0).abs() < 0.0001);
+        assert_eq!(kernel[0], kernel[6]);
+    }
+
+    #[test]
+    fn blurring_a_flat_canvas_leaves_it_unchanged() {
+        let mut canvas = Canvas::new(5, 5);
+        let grey = Colour::new(0.5, 0.5, 0.5);
+        for x in 0..5 {
+            for y in 0..5 {
+                canvas.write_pixel(x, y, &grey);
+            }
+        }
+
+        let blurred = gaussian_blur(&canvas, 2);
+
+        assert!(approx_equal(blurred.read_pixel(0, 0), grey));
+        assert!(approx_equal(blurred.read_pixel(2, 2), grey));
+    }
+
+    #[test]
+    fn blurring_spreads_a_single_pixel() {
+        let mut canvas = Canvas::new(5, 5);
+        canvas.write_pixel(2, 2, &Colour::new(1.0, 1.0, 1.0));
+
+        let blurred = gaussian_blur(&canvas, 1);
+
+        assert!(blurred.read_pixel(2, 2).r < 1.0);
+        assert!(blurred.read_pixel(1, 2).r > 0.0);
+        assert_eq!(blurred.read_pixel(1, 2), blurred.read_pixel(3, 2));
+    }
+
+    #[test]
+    fn bloom_adds_glow_around_bright_pixels() {
+        let mut canvas = Canvas::new(5, 5);
+        canvas.write_pixel(2, 2, &Colour::new(10.0, 10.0, 10.0));
+
+        let result = bloom(&canvas, 1.0, 1, 1.0);
+
+        assert!(result.read_pixel(1, 2).r > 0.0);
+        assert_eq!(result.read_pixel(0, 4), Colour::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn vignette_darkens_edges_more_than_centre() {
+        let mut canvas = Canvas::new(5, 5);
+        let white = Colour::new(1.0, 1.0, 1.0);
+        for x in 0..5 {
+            for y in 0..5 {
+                canvas.write_pixel(x, y, &white);
+            }
+        }
+
+        let result = vignette(&canvas, 0.5);
+
+        assert!(result.read_pixel(0, 0).r < result.read_pixel(2, 2).r);
+        assert!(approx_equal(result.read_pixel(2, 2), white));
+    }
+
+    #[test]
+    fn chromatic_aberration_keeps_green_channel() {
+        let mut canvas = Canvas::new(3, 3);
+        canvas.write_pixel(0, 0, &Colour::new(1.0, 0.5, 1.0));
+
+        let result = chromatic_aberration(&canvas, 0.5);
+
+        assert_eq!(result.read_pixel(0, 0).g, 0.5);
+    }
+}
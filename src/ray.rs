@@ -22,6 +22,14 @@ impl Ray {
             direction: &transformation * self.direction
         }
     }
+
+    // borrows the matrix so hot paths like intersection don't have to clone it
+    pub fn transform_by(&self, transformation: &Matrix) -> Ray {
+        Ray {
+            origin: transformation * self.origin,
+            direction: transformation * self.direction
+        }
+    }
 }
 
 #[cfg(test)]
@@ -70,4 +78,16 @@ mod tests {
         assert_eq!(r2.origin, Tuple::point(2.0, 6.0, 12.0));
         assert_eq!(r2.direction, Tuple::vector(0.0, 3.0, 0.0));
     }
+
+    #[test]
+    fn transforming_by_reference_matches_by_value() {
+        let r = Ray::new(Tuple::point(1.0, 2.0, 3.0), Tuple::vector(0.0, 1.0, 0.0));
+        let m = Matrix::scaling(2.0, 3.0, 4.0).translate(1.0, 0.0, 0.0);
+
+        let r2 = r.transform_by(&m);
+        let r3 = r.transform(m);
+
+        assert_eq!(r2.origin, r3.origin);
+        assert_eq!(r2.direction, r3.direction);
+    }
 }
@@ -1,29 +1,96 @@
-use crate::{Matrix, Tuple, Ray};
-use std::cell::Cell;
+use crate::{Material, Matrix, Tuple, Ray, util};
+use smallvec::SmallVec;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::sync::atomic::{self, AtomicU32};
 
-thread_local! {
-    static NEXT_ID_COUNTER: Cell<u32> = Cell::new(0);
+static NEXT_ID_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+// shapes return at most a handful of intersections, so keep them off the heap
+pub type Intersections<'a> = SmallVec<[Intersection<'a>; 4]>;
+
+#[derive(Debug, PartialEq)]
+pub enum TransformError {
+    NotInvertible { id: u32 },
+}
+
+impl fmt::Display for TransformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransformError::NotInvertible { id } => {
+                write!(f, "transform of sphere {} is not invertible", id)
+            }
+        }
+    }
 }
 
+impl std::error::Error for TransformError {}
+
 #[derive(Debug, PartialEq)]
 pub struct Sphere {
     id: u32,
-    pub transform: Matrix
+    transform: Matrix,
+    inverse: Matrix,
+    pub material: Material,
+    pub back_material: Option<Material>,
+    pub double_sided: bool,
 }
 
 impl Sphere {
     pub fn new() -> Self {
-        let id = NEXT_ID_COUNTER.with(|next_id| {
-            let next = next_id.get();
-            next_id.set(next + 1);
-            next
-        });
+        let id = NEXT_ID_COUNTER.fetch_add(1, atomic::Ordering::Relaxed);
         let transform = Matrix::identity();
-        Sphere{id, transform}
+        let inverse = Matrix::identity();
+        let material = Material::new();
+        Sphere{id, transform, inverse, material, back_material: None, double_sided: true}
+    }
+
+    pub fn with_transform(self, transform: Matrix) -> Self {
+        self.try_with_transform(transform).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_with_transform(mut self, transform: Matrix) -> Result<Self, TransformError> {
+        self.set_transform(transform)?;
+        Ok(self)
+    }
+
+    pub fn transform(&self) -> &Matrix {
+        &self.transform
     }
 
-    pub fn intersect(&self, orig_ray: &Ray) -> Vec<Intersection> {
-        let ray = orig_ray.transform(self.transform.invert());
+    // the inverse is cached here so that a singular transform is reported when
+    // it is assigned rather than when the sphere is first rendered
+    pub fn set_transform(&mut self, transform: Matrix) -> Result<(), TransformError> {
+        let inverse = transform.try_invert()
+            .ok_or(TransformError::NotInvertible { id: self.id })?;
+
+        self.transform = transform;
+        self.inverse = inverse;
+        Ok(())
+    }
+
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    pub fn with_back_material(mut self, material: Material) -> Self {
+        self.back_material = Some(material);
+        self
+    }
+
+    pub fn with_double_sided(mut self, double_sided: bool) -> Self {
+        self.double_sided = double_sided;
+        self
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn intersect(&self, orig_ray: &Ray) -> Intersections<'_> {
+        let ray = orig_ray.transform_by(&self.inverse);
 
         let sphere_to_ray = ray.origin - Tuple::point(0.0, 0.0, 0.0);
 
@@ -31,26 +98,63 @@ impl Sphere {
         let b = 2.0 * Tuple::dot(&ray.direction, &sphere_to_ray);
         let c = Tuple::dot(&sphere_to_ray, &sphere_to_ray) - 1.0;
 
-        let discriminant = (b * b) - (4.0 * a * c);
+        match util::solve_quadratic(a, b, c) {
+            Some((t1, t2)) => [t1, t2].iter()
+                .filter(|&&t| self.double_sided || !is_back_face(&ray, t))
+                .map(|&t| Intersection::new(t, self))
+                .collect(),
+            None => Intersections::new(),
+        }
+    }
 
-        if discriminant < 0.0 {
-            vec!()
-        } else {
-            let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
-            let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
+    // the eye sees the back face when it is on the opposite side to the normal
+    pub fn material_facing(&self, eyev: &Tuple, normalv: &Tuple) -> &Material {
+        match &self.back_material {
+            Some(back) if Tuple::dot(eyev, normalv) < 0.0 => back,
+            _ => &self.material,
+        }
+    }
+
+    pub fn normal_at(&self, world_point: &Tuple) -> Tuple {
+        let object_point = &self.inverse * world_point;
+        let object_normal = object_point - Tuple::point(0.0, 0.0, 0.0);
 
-            vec!(Intersection::new(t1, &self), Intersection::new(t2, &self))
+        let mut world_normal = self.inverse.transpose() * object_normal;
+        // the transpose of the inverse leaves junk in w if there was a translation
+        world_normal.w = 0.0;
+
+        world_normal.normalise()
+    }
+}
+
+// a copy can be changed independently, so it is a new object with its own id
+impl Clone for Sphere {
+    fn clone(&self) -> Self {
+        Sphere {
+            id: NEXT_ID_COUNTER.fetch_add(1, atomic::Ordering::Relaxed),
+            transform: self.transform.clone(),
+            inverse: self.inverse.clone(),
+            material: self.material,
+            back_material: self.back_material,
+            double_sided: self.double_sided,
         }
     }
 }
 
+// the sign of this dot product survives the object transform, so the
+// test can be done with the object space ray
+fn is_back_face(object_ray: &Ray, t: f32) -> bool {
+    let object_normal = object_ray.position(t) - Tuple::point(0.0, 0.0, 0.0);
+    Tuple::dot(&object_ray.direction, &object_normal) > 0.0
+}
+
 impl Default for Sphere {
     fn default() -> Self {
         Self::new()
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Intersection<'a> {
     pub t: f32,
     pub object: &'a Sphere,
@@ -68,14 +172,131 @@ pub fn hit<'a>(intersections: &'a [Intersection]) -> Option<&'a Intersection<'a>
                  .min_by(|a, b| a.t.partial_cmp(&b.t).unwrap())
 }
 
+// merges lists that are each sorted by t into a single sorted list
+pub fn merge<'a, I>(lists: Vec<I>) -> Vec<Intersection<'a>>
+where
+    I: IntoIterator<Item = Intersection<'a>>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let mut heads = BinaryHeap::with_capacity(lists.len());
+    let mut iters: Vec<_> = lists.into_iter().map(IntoIterator::into_iter).collect();
+
+    let total = iters.iter().map(ExactSizeIterator::len).sum();
+    let mut merged = Vec::with_capacity(total);
+
+    for (list, iter) in iters.iter_mut().enumerate() {
+        if let Some(intersection) = iter.next() {
+            heads.push(Head { intersection, list });
+        }
+    }
+
+    while let Some(Head { intersection, list }) = heads.pop() {
+        merged.push(intersection);
+        if let Some(next) = iters[list].next() {
+            heads.push(Head { intersection: next, list });
+        }
+    }
+
+    merged
+}
+
+pub fn filter_range<'a, I>(intersections: I, t_min: f32, t_max: f32) -> Vec<Intersection<'a>>
+where
+    I: IntoIterator<Item = Intersection<'a>>,
+{
+    intersections.into_iter()
+                 .filter(|i| i.t >= t_min && i.t <= t_max)
+                 .collect()
+}
+
+// entry in the merge heap, ordered so that the smallest t is popped first
+struct Head<'a> {
+    intersection: Intersection<'a>,
+    list: usize,
+}
+
+impl PartialEq for Head<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head<'_> {}
+
+impl PartialOrd for Head<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Head<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.intersection.t.total_cmp(&self.intersection.t)
+            .then_with(|| other.list.cmp(&self.list))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Colour;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    // counts allocations per thread so parallel tests don't disturb each other
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations() -> usize {
+        ALLOCATIONS.with(Cell::get)
+    }
 
     #[test]
     fn creating_new_matrix() {
         let sphere = Sphere::new();
-        assert_eq!(sphere.transform, Matrix::identity());
+        assert_eq!(sphere.transform(), &Matrix::identity());
+    }
+
+    #[test]
+    fn spheres_have_unique_ids() {
+        let a = Sphere::new();
+        let b = Sphere::new();
+        assert_ne!(a.id(), b.id());
+    }
+
+    #[test]
+    fn spheres_created_on_different_threads_have_unique_ids() {
+        let handles: Vec<_> = (0..4)
+            .map(|_| std::thread::spawn(|| (0..100).map(|_| Sphere::new().id()).collect::<Vec<_>>()))
+            .collect();
+
+        let mut ids: Vec<u32> = handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        assert_eq!(ids.len(), 400);
     }
 
     #[test]
@@ -102,6 +323,29 @@ mod tests {
         assert_eq!(intersections[1].t, 5.0);
     }
 
+    #[test]
+    fn intersections_are_stored_inline() {
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let s = Sphere::new();
+
+        let xs = s.intersect(&r);
+
+        assert!(!xs.spilled());
+    }
+
+    #[test]
+    fn intersecting_does_not_allocate() {
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let s = Sphere::new().with_transform(Matrix::scaling(2.0, 2.0, 2.0));
+
+        let before = allocations();
+        let xs = s.intersect(&r);
+        let after = allocations();
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(after, before);
+    }
+
     #[test]
     fn ray_missing_sphere() {
         let r = Ray::new(Tuple::point(0.0, 2.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
@@ -112,6 +356,16 @@ mod tests {
         assert_eq!(intersections.len(), 0);
     }
 
+    #[test]
+    fn ray_without_direction_misses_sphere() {
+        let r = Ray::new(Tuple::point(0.0, 10.0, -5.0), Tuple::vector(0.0, 0.0, 0.0));
+        let s = Sphere::new();
+
+        let xs = s.intersect(&r);
+
+        assert!(xs.is_empty());
+    }
+
     #[test]
     fn ray_whose_origin_is_within_sphere() {
         let r = Ray::new(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
@@ -136,6 +390,48 @@ mod tests {
         assert_eq!(intersections[1].t, -4.0);
     }
 
+    #[test]
+    fn spheres_are_double_sided_by_default() {
+        let s = Sphere::new();
+        assert!(s.double_sided);
+    }
+
+    #[test]
+    fn single_sided_sphere_skips_back_face_from_outside() {
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let s = Sphere::new().with_double_sided(false);
+
+        let xs = s.intersect(&r);
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 4.0);
+    }
+
+    #[test]
+    fn single_sided_sphere_is_invisible_from_inside() {
+        let r = Ray::new(Tuple::point(0.0, 0.0, 0.0), Tuple::vector(0.0, 0.0, 1.0));
+        let s = Sphere::new().with_double_sided(false);
+
+        let xs = s.intersect(&r);
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, -1.0);
+        assert_eq!(hit(&xs), None);
+    }
+
+    #[test]
+    fn single_sided_culling_respects_transform() {
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let s = Sphere::new()
+            .with_transform(Matrix::scaling(2.0, 2.0, 2.0))
+            .with_double_sided(false);
+
+        let xs = s.intersect(&r);
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 3.0);
+    }
+
     #[test]
     fn intersect_sets_correct_object() {
         let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
@@ -192,11 +488,55 @@ mod tests {
         assert_eq!(*i.unwrap(), Intersection::new(2.0, &s));
     }
 
+    #[test]
+    fn merge_produces_sorted_list() {
+        let s = Sphere::new();
+        let a = vec!(Intersection::new(-1.0, &s), Intersection::new(4.0, &s));
+        let b = vec!(Intersection::new(0.5, &s), Intersection::new(6.0, &s));
+        let c = vec!();
+        let d = vec!(Intersection::new(2.0, &s), Intersection::new(3.0, &s));
+
+        let merged = merge(vec!(a, b, c, d));
+        let ts: Vec<f32> = merged.iter().map(|i| i.t).collect();
+
+        assert_eq!(ts, vec!(-1.0, 0.5, 2.0, 3.0, 4.0, 6.0));
+    }
+
+    #[test]
+    fn merge_keeps_objects() {
+        let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
+        let near = Sphere::new();
+        let far = Sphere::new().with_transform(Matrix::translation(0.0, 0.0, 3.0));
+
+        let merged = merge(vec!(far.intersect(&r), near.intersect(&r)));
+
+        assert_eq!(merged.len(), 4);
+        assert_eq!(merged[0].object, &near);
+        assert_eq!(merged[1].t, 6.0);
+        assert_eq!(merged[1].object, &near);
+        assert_eq!(merged[3].object, &far);
+    }
+
+    #[test]
+    fn filter_range_keeps_intersections_within_bounds() {
+        let s = Sphere::new();
+        let xs = vec!(
+            Intersection::new(-1.0, &s),
+            Intersection::new(1.0, &s),
+            Intersection::new(2.0, &s),
+            Intersection::new(5.0, &s)
+        );
+
+        let filtered = filter_range(xs, 0.0, 2.0);
+
+        assert_eq!(filtered, vec!(Intersection::new(1.0, &s), Intersection::new(2.0, &s)));
+    }
+
     #[test]
     fn intersecting_a_scaled_sphere_with_ray() {
         let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
         let mut s = Sphere::new();
-        s.transform = Matrix::scaling(2.0, 2.0, 2.0);
+        s.set_transform(Matrix::scaling(2.0, 2.0, 2.0)).unwrap();
         let xs = s.intersect(&r);
 
         assert_eq!(xs.len(), 2);
@@ -208,9 +548,162 @@ mod tests {
     fn intersecting_a_translated_sphere_with_ray() {
         let r = Ray::new(Tuple::point(0.0, 0.0, -5.0), Tuple::vector(0.0, 0.0, 1.0));
         let mut s = Sphere::new();
-        s.transform = Matrix::translation(5.0, 0.0, 0.0);
+        s.set_transform(Matrix::translation(5.0, 0.0, 0.0)).unwrap();
         let xs = s.intersect(&r);
 
         assert_eq!(xs.len(), 0);
     }
+
+    #[test]
+    fn singular_transform_is_rejected() {
+        let mut s = Sphere::new();
+
+        let result = s.set_transform(Matrix::scaling(0.0, 1.0, 1.0));
+
+        assert_eq!(result, Err(TransformError::NotInvertible { id: s.id() }));
+        assert_eq!(s.transform(), &Matrix::identity());
+    }
+
+    #[test]
+    fn builder_rejects_singular_transform() {
+        let s = Sphere::new();
+        let id = s.id();
+
+        let result = s.try_with_transform(Matrix::scaling(1.0, 0.0, 1.0));
+
+        assert_eq!(result.err(), Some(TransformError::NotInvertible { id }));
+    }
+
+    #[test]
+    #[should_panic(expected = "is not invertible")]
+    fn infallible_builder_panics_on_singular_transform() {
+        Sphere::new().with_transform(Matrix::scaling(1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn builder_sets_transform_and_material() {
+        let mut m = Material::new();
+        m.ambient = 1.0;
+
+        let s = Sphere::new()
+            .with_transform(Matrix::scaling(2.0, 2.0, 2.0))
+            .with_material(m);
+
+        assert_eq!(s.transform(), &Matrix::scaling(2.0, 2.0, 2.0));
+        assert_eq!(s.material, m);
+    }
+
+    #[test]
+    fn cloned_sphere_is_a_new_object() {
+        let s = Sphere::new().with_transform(Matrix::translation(1.0, 0.0, 0.0));
+        let c = s.clone();
+
+        assert_ne!(c.id(), s.id());
+        assert_eq!(c.transform(), s.transform());
+        assert_eq!(c.material, s.material);
+    }
+
+    #[test]
+    fn front_material_used_without_back_material() {
+        let s = Sphere::new().with_material(Material::new().with_ambient(1.0));
+        let eyev = Tuple::vector(0.0, 0.0, -1.0);
+        let normalv = Tuple::vector(0.0, 0.0, 1.0);
+
+        assert_eq!(s.material_facing(&eyev, &normalv), &s.material);
+    }
+
+    #[test]
+    fn back_material_selected_by_facing() {
+        let front = Material::new().with_colour(Colour::new(1.0, 0.0, 0.0));
+        let back = Material::new().with_colour(Colour::new(0.0, 0.0, 1.0));
+        let s = Sphere::new().with_material(front).with_back_material(back);
+        let normalv = Tuple::vector(0.0, 0.0, -1.0);
+
+        let outside_eye = Tuple::vector(0.0, 0.0, -1.0);
+        let inside_eye = Tuple::vector(0.0, 0.0, 1.0);
+
+        assert_eq!(s.material_facing(&outside_eye, &normalv), &front);
+        assert_eq!(s.material_facing(&inside_eye, &normalv), &back);
+    }
+
+    #[test]
+    fn normal_on_x_axis() {
+        let s = Sphere::new();
+        let n = s.normal_at(&Tuple::point(1.0, 0.0, 0.0));
+        assert_eq!(n, Tuple::vector(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn normal_on_y_axis() {
+        let s = Sphere::new();
+        let n = s.normal_at(&Tuple::point(0.0, 1.0, 0.0));
+        assert_eq!(n, Tuple::vector(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn normal_on_z_axis() {
+        let s = Sphere::new();
+        let n = s.normal_at(&Tuple::point(0.0, 0.0, 1.0));
+        assert_eq!(n, Tuple::vector(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn normal_at_nonaxial_point() {
+        let s = Sphere::new();
+        let v = 3_f32.sqrt() / 3.0;
+        let n = s.normal_at(&Tuple::point(v, v, v));
+        assert_eq!(n, Tuple::vector(v, v, v));
+    }
+
+    #[test]
+    fn normal_is_normalised() {
+        let s = Sphere::new();
+        let v = 3_f32.sqrt() / 3.0;
+        let n = s.normal_at(&Tuple::point(v, v, v));
+        assert_eq!(n, n.normalise());
+    }
+
+    #[test]
+    fn normal_on_translated_sphere() {
+        let mut s = Sphere::new();
+        s.set_transform(Matrix::translation(0.0, 1.0, 0.0)).unwrap();
+        let v = std::f32::consts::FRAC_1_SQRT_2;
+        let n = s.normal_at(&Tuple::point(0.0, 1.0 + v, -v));
+
+        assert!(approx_equal(n, Tuple::vector(0.0, v, -v)));
+    }
+
+    #[test]
+    fn normal_on_transformed_sphere() {
+        let mut s = Sphere::new();
+        s.set_transform(Matrix::identity()
+            .rotate_z(std::f32::consts::PI / 5.0)
+            .scale(1.0, 0.5, 1.0)).unwrap();
+        let v = 2_f32.sqrt() / 2.0;
+        let n = s.normal_at(&Tuple::point(0.0, v, -v));
+
+        assert!(approx_equal(n, Tuple::vector(0.0, 0.97014, -0.24254)));
+    }
+
+    #[test]
+    fn sphere_has_default_material() {
+        let s = Sphere::new();
+        assert_eq!(s.material, Material::new());
+    }
+
+    #[test]
+    fn sphere_may_be_assigned_material() {
+        let mut s = Sphere::new();
+        let mut m = Material::new();
+        m.ambient = 1.0;
+        s.material = m;
+        assert_eq!(s.material, m);
+    }
+
+    fn approx_equal(a: Tuple, b: Tuple) -> bool {
+        (a.x - b.x).abs() < 0.0001
+            && (a.y - b.y).abs() < 0.0001
+            && (a.z - b.z).abs() < 0.0001
+            && (a.w - b.w).abs() < 0.0001
+    }
 }
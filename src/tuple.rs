@@ -24,7 +24,7 @@ impl Tuple {
     }
 
     pub fn is_point(&self) -> bool {
-        (self.w - 1.0).abs() < std::f32::EPSILON
+        (self.w - 1.0).abs() < f32::EPSILON
     }
 
     pub fn is_vector(&self) -> bool {
@@ -46,6 +46,15 @@ impl Tuple {
         )
     }
 
+    pub fn try_normalise(&self) -> Option<Self> {
+        let magnitude = self.magnitude();
+        if magnitude > 0.0 && magnitude.is_finite() {
+            Some(*self / magnitude)
+        } else {
+            None
+        }
+    }
+
     pub fn dot(a: &Self, b: &Self) -> f32 {
         a.x * b.x + a.y * b.y + a.z * b.z + a.w * b.w
     }
@@ -57,6 +66,10 @@ impl Tuple {
             a.x * b.y - a.y * b.x,
         )
     }
+
+    pub fn reflect(incoming: &Self, normal: &Self) -> Self {
+        *incoming - *normal * 2.0 * Tuple::dot(incoming, normal)
+    }
 }
 
 impl PartialEq for Tuple {
@@ -151,7 +164,7 @@ mod tests {
         let v = Tuple::vector(1.3, 1.5, 45.8);
 
         assert!(p.is_point());
-        assert!(v.is_point() == false);
+        assert!(!v.is_point());
     }
 
     #[test]
@@ -159,7 +172,7 @@ mod tests {
         let p = Tuple::point(1.3, 1.5, 45.8);
         let v = Tuple::vector(1.3, 1.5, 45.8);
 
-        assert!(p.is_vector() == false);
+        assert!(!p.is_vector());
         assert!(v.is_vector());
     }
 
@@ -262,6 +275,24 @@ mod tests {
         assert_eq!(v.normalise().magnitude(), 1.0);
     }
 
+    #[test]
+    fn try_normalise_works_for_nonzero_vector() {
+        let v = Tuple::vector(4.0, 0.0, 0.0);
+        assert_eq!(v.try_normalise(), Some(Tuple::vector(1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn try_normalise_rejects_zero_vector() {
+        let v = Tuple::vector(0.0, 0.0, 0.0);
+        assert_eq!(v.try_normalise(), None);
+    }
+
+    #[test]
+    fn try_normalise_rejects_non_finite_vector() {
+        let v = Tuple::vector(f32::INFINITY, 0.0, 0.0);
+        assert_eq!(v.try_normalise(), None);
+    }
+
     #[test]
     fn dot_product_works() {
         let a = Tuple::vector(1.0, 2.0, 3.0);
@@ -278,4 +309,24 @@ mod tests {
         assert_eq!(Tuple::cross(&a, &b), Tuple::vector(-1.0, 2.0, -1.0));
         assert_eq!(Tuple::cross(&b, &a), Tuple::vector(1.0, -2.0, 1.0));
     }
+
+    #[test]
+    fn reflecting_vector_approaching_at_45_degrees() {
+        let v = Tuple::vector(1.0, -1.0, 0.0);
+        let n = Tuple::vector(0.0, 1.0, 0.0);
+
+        assert_eq!(Tuple::reflect(&v, &n), Tuple::vector(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn reflecting_vector_off_slanted_surface() {
+        let v = Tuple::vector(0.0, -1.0, 0.0);
+        let n = Tuple::vector(2_f32.sqrt() / 2.0, 2_f32.sqrt() / 2.0, 0.0);
+
+        let r = Tuple::reflect(&v, &n);
+
+        assert!((r.x - 1.0).abs() < 0.0001);
+        assert!(r.y.abs() < 0.0001);
+        assert_eq!(r.z, 0.0);
+    }
 }
@@ -1,5 +1,39 @@
 pub fn float_equality(a: f32, b: f32) -> bool {
-    (a - b).abs() <= std::f32::EPSILON
+    (a - b).abs() <= f32::EPSILON
+}
+
+pub fn solve_quadratic(a: f32, b: f32, c: f32) -> Option<(f32, f32)> {
+    // with no squared term the equation is linear, with at most one root
+    if a == 0.0 {
+        if b == 0.0 {
+            return None;
+        }
+        let t = -c / b;
+        return Some((t, t));
+    }
+
+    let discriminant = (b * b) - (4.0 * a * c);
+
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    // the second root comes from the citardauq form, which avoids cancellation
+    // between -b and the square root when b*b is much larger than 4ac
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0.0 {
+        // with a non-zero this is only possible when b and c are both zero
+        return Some((0.0, 0.0));
+    }
+
+    let t1 = q / a;
+    let t2 = c / q;
+
+    if t1 <= t2 {
+        Some((t1, t2))
+    } else {
+        Some((t2, t1))
+    }
 }
 
 #[cfg(test)]
@@ -14,4 +48,37 @@ mod tests {
 
         assert!(float_equality(a, b));
     }
+
+    #[test]
+    fn solve_quadratic_with_two_roots() {
+        assert_eq!(solve_quadratic(1.0, -3.0, 2.0), Some((1.0, 2.0)));
+        assert_eq!(solve_quadratic(1.0, 3.0, 2.0), Some((-2.0, -1.0)));
+    }
+
+    #[test]
+    fn solve_quadratic_with_repeated_root() {
+        assert_eq!(solve_quadratic(1.0, -2.0, 1.0), Some((1.0, 1.0)));
+        assert_eq!(solve_quadratic(1.0, 0.0, 0.0), Some((0.0, 0.0)));
+    }
+
+    #[test]
+    fn solve_quadratic_with_no_real_roots() {
+        assert_eq!(solve_quadratic(1.0, 0.0, 1.0), None);
+    }
+
+    #[test]
+    fn solve_quadratic_with_degenerate_input() {
+        assert_eq!(solve_quadratic(0.0, 2.0, -4.0), Some((2.0, 2.0)));
+        assert_eq!(solve_quadratic(0.0, 0.0, 1.0), None);
+        assert_eq!(solve_quadratic(0.0, 0.0, 0.0), None);
+    }
+
+    #[test]
+    fn solve_quadratic_keeps_precision_for_small_root() {
+        // roots are 1e-4 and 1e4; the naive formula loses the small one
+        let (t1, t2) = solve_quadratic(1.0, -10000.0, 1.0).unwrap();
+
+        assert!((t1 - 0.0001).abs() / 0.0001 < 0.001);
+        assert!((t2 - 10000.0).abs() < 0.01);
+    }
 }